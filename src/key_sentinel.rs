@@ -17,9 +17,12 @@
 
 use lru_time_cache::LruCache;
 use sodiumoxide::crypto::sign;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
 use std::collections::{BTreeSet, BTreeMap};
 use key_store::KeyStore;
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 
 #[allow(dead_code)]
 const MAX_REQUEST_COUNT: usize = 1000;
@@ -32,9 +35,137 @@ pub trait IdTrait<NameType> {
     fn public_key(&self) -> sign::PublicKey;
 }
 
+/// An `IdTrait` whose holder also knows its own Shamir share index, needed to check the held
+/// public key against a group's verifiable-secret-sharing commitment.
+pub trait VssIdTrait<NameType>: IdTrait<NameType> {
+    fn share_index(&self) -> u64;
+}
+
 pub trait GroupClaimTrait<IdTrait> {
     fn group_identities(&self) -> Vec<IdTrait>;
     fn verify_public_key(&self, _: &sign::PublicKey) -> bool;
+
+    /// This claim's view of every dealer's VSS commitment `phi_0..phi_{t-1}` for the group's
+    /// polynomial, one vector per dealer.
+    fn dealer_commitments(&self) -> Vec<Vec<sign::PublicKey>>;
+
+    /// This claim's view of the group's aggregated commitment `group[k] = sum_dealers
+    /// commitment_dealer[k]`, or `None` if the dealers disagree on the polynomial's degree
+    /// (commitment vector length) or any commitment point fails to decompress.
+    ///
+    /// This is merely what `self`'s sender *asserts* the group commitment to be — a Byzantine
+    /// sender can pick any commitment it likes, so callers must never trust it on its own. See
+    /// `KeySentinel::add_identities_verified`, which only locks in a commitment once enough
+    /// independent senders agree on the identical value.
+    fn group_commitment(&self) -> Option<Vec<sign::PublicKey>> {
+        let mut dealers = self.dealer_commitments().into_iter();
+        let first = match dealers.next() {
+            Some(commitment) => commitment,
+            None => return None,
+        };
+
+        let degree = first.len();
+        let mut aggregated = Vec::with_capacity(degree);
+        for phi_k in &first {
+            aggregated.push(match decompress_edwards(phi_k) {
+                Some(point) => point,
+                None => return None,
+            });
+        }
+
+        for commitment in dealers {
+            if commitment.len() != degree {
+                return None;
+            }
+            for (acc, phi_k) in aggregated.iter_mut().zip(commitment.iter()) {
+                let point = match decompress_edwards(phi_k) {
+                    Some(point) => point,
+                    None => return None,
+                };
+                *acc = *acc + point;
+            }
+        }
+
+        Some(aggregated.iter().map(compress_edwards).collect())
+    }
+}
+
+/// `None` if `key`'s 32 bytes are not the canonical encoding of an Edwards point — dealer
+/// commitments and member public keys are attacker-controlled and must be rejected, not unwrapped.
+fn decompress_edwards(key: &sign::PublicKey) -> Option<EdwardsPoint> {
+    CompressedEdwardsY::from_slice(key.as_ref()).decompress()
+}
+
+fn compress_edwards(point: &EdwardsPoint) -> sign::PublicKey {
+    sign::PublicKey::from_slice(point.compress().as_bytes())
+        .expect("a compressed Edwards point is always 32 bytes")
+}
+
+/// Does `public_key` lie on the polynomial described by `commitment` at `share_index`, i.e. is it
+/// `sum_k (share_index^k) * phi_k`? `false` if `commitment` is empty or any point in it, or
+/// `public_key` itself, fails to decompress.
+fn verify_share(commitment: &[sign::PublicKey], share_index: u64, public_key: &sign::PublicKey) -> bool {
+    if commitment.is_empty() {
+        return false;
+    }
+
+    let target = match decompress_edwards(public_key) {
+        Some(point) => point,
+        None => return false,
+    };
+
+    let index = Scalar::from(share_index);
+    let mut expected = match decompress_edwards(&commitment[0]) {
+        Some(point) => point,
+        None => return false,
+    };
+    let mut power = Scalar::one();
+    for phi_k in &commitment[1..] {
+        let point = match decompress_edwards(phi_k) {
+            Some(point) => point,
+            None => return false,
+        };
+        power = power * index;
+        expected = expected + point * power;
+    }
+
+    expected == target
+}
+
+/// A byte-for-byte key for a claimed commitment vector, used to tally independent senders'
+/// agreement on it before it is trusted. `sign::PublicKey` has no `Ord` impl of its own.
+fn commitment_fingerprint(commitment: &[sign::PublicKey]) -> Vec<u8> {
+    commitment.iter().flat_map(|key| key.as_ref().iter().cloned()).collect()
+}
+
+/// One request's in-progress accumulation: the keys and claims contributed so far, when the
+/// accumulation was started (for expiry), the senders excluded as equivocators, and — for
+/// `add_identities_verified` — the tally of independent senders asserting each candidate VSS
+/// group commitment, and the one (if any) that has earned enough agreement to be trusted.
+struct Accumulation<Name, GroupClaim>
+        where Name:       Eq + PartialOrd + Ord + Clone,
+              GroupClaim:  Eq + PartialOrd + Ord + Clone, {
+    keys: KeyStore<Name>,
+    claims: Map<Name, Set<GroupClaim>>,
+    inserted: Instant,
+    byzantine: Set<Name>,
+    commitment_votes: Map<Vec<u8>, Set<Name>>,
+    trusted_commitment: Option<Vec<sign::PublicKey>>,
+}
+
+impl<Name, GroupClaim> Accumulation<Name, GroupClaim>
+        where Name:       Eq + PartialOrd + Ord + Clone,
+              GroupClaim:  Eq + PartialOrd + Ord + Clone, {
+    fn new(keys_threshold: usize) -> Accumulation<Name, GroupClaim> {
+        Accumulation {
+            keys: KeyStore::new(keys_threshold),
+            claims: Map::new(),
+            inserted: Instant::now(),
+            byzantine: Set::new(),
+            commitment_votes: Map::new(),
+            trusted_commitment: None,
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -43,9 +174,10 @@ pub struct KeySentinel<Request, Name, IdType, GroupClaim>
               Name:    Eq + PartialOrd + Ord + Clone,
               IdType:  Eq + PartialOrd + Ord + Clone + IdTrait<Name>,
               GroupClaim:  Eq + PartialOrd + Ord + Clone + GroupClaimTrait<IdType>, {
-    cache: LruCache<Request, (KeyStore<Name>, Map<Name, Set<GroupClaim>>)>,
+    cache: LruCache<Request, Accumulation<Name, GroupClaim>>,
     claim_threshold: usize,
     keys_threshold: usize,
+    expiry_duration: Option<Duration>,
     phantom: PhantomData<IdType>,
 }
 
@@ -62,47 +194,271 @@ impl<Request, Name, IdType, GroupClaim> KeySentinel<Request, Name, IdType, Group
             cache: LruCache::with_capacity(MAX_REQUEST_COUNT),
             claim_threshold: claim_threshold,
             keys_threshold: keys_threshold,
+            expiry_duration: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// As `new`, but an accumulation that never reaches `claim_threshold` is dropped after
+    /// `expiry_duration` rather than lingering until evicted by capacity pressure alone.
+    #[allow(dead_code)]
+    pub fn with_expiry_duration(claim_threshold: usize, keys_threshold: usize, expiry_duration: Duration)
+            -> KeySentinel<Request, Name, IdType, GroupClaim> {
+        KeySentinel {
+            cache: LruCache::with_expiry_duration(expiry_duration),
+            claim_threshold: claim_threshold,
+            keys_threshold: keys_threshold,
+            expiry_duration: Some(expiry_duration),
             phantom: PhantomData,
         }
     }
 
+    /// As `with_expiry_duration`, additionally bounding the cache to `capacity` entries.
+    #[allow(dead_code)]
+    pub fn with_expiry_duration_and_capacity(claim_threshold: usize,
+                                             keys_threshold: usize,
+                                             expiry_duration: Duration,
+                                             capacity: usize)
+            -> KeySentinel<Request, Name, IdType, GroupClaim> {
+        KeySentinel {
+            cache: LruCache::with_expiry_duration_and_capacity(expiry_duration, capacity),
+            claim_threshold: claim_threshold,
+            keys_threshold: keys_threshold,
+            expiry_duration: Some(expiry_duration),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Forces eviction of any accumulation older than the configured expiry duration and returns
+    /// the `Request`s that timed out. A no-op returning an empty `Vec` when no expiry is set.
+    #[allow(dead_code)]
+    pub fn cleanup(&mut self) -> Vec<Request> {
+        let expiry_duration = match self.expiry_duration {
+            Some(expiry_duration) => expiry_duration,
+            None => return Vec::new(),
+        };
+
+        let now = Instant::now();
+        let timed_out = self.cache.iter()
+            .filter(|&(_, accumulation)| now.duration_since(accumulation.inserted) >= expiry_duration)
+            .map(|(request, _)| request.clone())
+            .collect::<Vec<_>>();
+
+        for request in &timed_out {
+            self.cache.remove(request);
+        }
+
+        timed_out
+    }
+
+    /// Accumulates `claim` from `sender`. A `sender` who submits two claims that disagree for the
+    /// same `request` is flagged as an equivocator: its previously contributed keys are dropped
+    /// from the `KeyStore` and it is excluded from `try_selecting_group` from then on, even across
+    /// later calls for the same `request` (`byzantine_senders` records the exclusion). Returns the
+    /// resolved group, if any, alongside the full set of equivocators detected for `request` so far.
     #[allow(dead_code)]
     pub fn add_identities(&mut self,
                           request : Request,
                           sender  : Name,
                           claim   : GroupClaim)
-        -> Option<(Request, Vec<IdType>)> {
+        -> (Option<(Request, Vec<IdType>)>, Set<Name>) {
 
-        let retval = {
+        let (selection, byzantine_senders) = {
             let keys_threshold = self.keys_threshold;
-            let keys_and_claims
+            let accumulation
                 = self.cache.entry(request.clone())
-                            .or_insert_with(||(KeyStore::new(keys_threshold), Map::new()));
+                            .or_insert_with(|| Accumulation::new(keys_threshold));
+
+            if !accumulation.byzantine.contains(&sender) {
+                if Self::equivocates(&accumulation.claims, &sender, &claim) {
+                    accumulation.byzantine.insert(sender.clone());
+                    // `remove_keys_from` drops every entry `sender` vouched for, across every
+                    // identity it contributed a key under, not just an entry for its own name —
+                    // exactly what's needed to undo an equivocator's contributions wholesale.
+                    accumulation.keys.remove_keys_from(&sender);
+                    accumulation.claims.remove(&sender);
+                } else {
+                    for id in claim.group_identities() {
+                        accumulation.keys.add_key(id.name(), sender.clone(), id.public_key());
+                    }
+                    accumulation.claims.entry(sender).or_insert_with(||Set::new()).insert(claim);
+                }
+            }
+
+            let selection = Self::try_selecting_group(&mut accumulation.keys, &accumulation.claims,
+                                                       &accumulation.byzantine, self.claim_threshold)
+                .map(|ids|(request.clone(), ids));
+
+            (selection, accumulation.byzantine.clone())
+        };
+
+        if selection.is_some() {
+            self.cache.remove(&request);
+        }
+
+        (selection, byzantine_senders)
+    }
 
-            let ref mut keys   = &mut keys_and_claims.0;
-            let ref mut claims = &mut keys_and_claims.1;
+    /// As `add_identities`, but for groups described by a VSS commitment: an identity whose public
+    /// key does not lie on the group's commitment polynomial is never inserted into the
+    /// `KeyStore`, so a Byzantine sender cannot smuggle in a key outside the group's polynomial.
+    ///
+    /// `claim.group_commitment()` is merely what `sender` itself asserts the commitment to be, and
+    /// a Byzantine sender can fabricate any polynomial it likes to "prove" an arbitrary key lies on
+    /// it. So the commitment is never trusted off a single claim: it is only locked in for
+    /// `request` once `keys_threshold` independent senders have each asserted the identical
+    /// commitment, at which point every key claimed so far is re-verified against it.
+    #[allow(dead_code)]
+    pub fn add_identities_verified(&mut self,
+                                   request : Request,
+                                   sender  : Name,
+                                   claim   : GroupClaim)
+        -> (Option<(Request, Vec<IdType>)>, Set<Name>)
+        where IdType: VssIdTrait<Name> {
+
+        let claimed_commitment = match claim.group_commitment() {
+            Some(commitment) => commitment,
+            None => return (None, self.cache.get(&request)
+                                          .map(|accumulation| accumulation.byzantine.clone())
+                                          .unwrap_or_else(Set::new)),
+        };
 
-            for id in claim.group_identities() {
-                keys.add_key(id.name(), sender.clone(), id.public_key());
+        let (selection, byzantine_senders) = {
+            let keys_threshold = self.keys_threshold;
+            let accumulation
+                = self.cache.entry(request.clone())
+                            .or_insert_with(|| Accumulation::new(keys_threshold));
+
+            if !accumulation.byzantine.contains(&sender) {
+                if Self::equivocates(&accumulation.claims, &sender, &claim) {
+                    accumulation.byzantine.insert(sender.clone());
+                    accumulation.keys.remove_keys_from(&sender);
+                    accumulation.claims.remove(&sender);
+                } else {
+                    accumulation.claims.entry(sender.clone()).or_insert_with(||Set::new()).insert(claim);
+
+                    if accumulation.trusted_commitment.is_none() {
+                        let voters = accumulation.commitment_votes
+                            .entry(commitment_fingerprint(&claimed_commitment))
+                            .or_insert_with(||Set::new());
+                        voters.insert(sender);
+
+                        if voters.len() >= keys_threshold {
+                            accumulation.trusted_commitment = Some(claimed_commitment);
+                        }
+                    }
+
+                    if let Some(trusted) = accumulation.trusted_commitment.clone() {
+                        accumulation.keys = Self::rebuild_verified_keys(&accumulation.claims, &trusted, keys_threshold);
+                    }
+                }
             }
 
-            claims.entry(sender).or_insert_with(||Set::new()).insert(claim);
+            let selection = Self::try_selecting_group(&mut accumulation.keys, &accumulation.claims,
+                                                       &accumulation.byzantine, self.claim_threshold)
+                .map(|ids|(request.clone(), ids));
 
-            Self::try_selecting_group(keys, claims, self.claim_threshold)
-                .map(|ids|(request, ids))
+            (selection, accumulation.byzantine.clone())
         };
 
-        retval.map(|(request, ids)| {
+        if selection.is_some() {
             self.cache.remove(&request);
-            (request, ids)
-        })
+        }
+
+        (selection, byzantine_senders)
+    }
+
+    /// Rebuilds a `KeyStore` from scratch out of every identity claimed so far that verifies
+    /// against `trusted_commitment`, run whenever the trusted commitment is established or a new
+    /// claim arrives, since the set of verifying identities is cheap to recompute outright.
+    fn rebuild_verified_keys(claims: &Map<Name, Set<GroupClaim>>,
+                             trusted_commitment: &[sign::PublicKey],
+                             keys_threshold: usize) -> KeyStore<Name>
+        where IdType: VssIdTrait<Name> {
+
+        let mut keys = KeyStore::new(keys_threshold);
+        for (sender, sender_claims) in claims {
+            for claim in sender_claims {
+                for id in claim.group_identities() {
+                    if verify_share(trusted_commitment, id.share_index(), &id.public_key()) {
+                        keys.add_key(id.name(), sender.clone(), id.public_key());
+                    }
+                }
+            }
+        }
+        keys
+    }
+
+    /// Migrates the `KeyStore` entries and claims contributed by `surviving` members from
+    /// `old_request`'s accumulation into a fresh one for `new_request`, so routine group churn
+    /// doesn't force a quorum to start from scratch. Keys and claims for departed members, and
+    /// already-excluded equivocators, are dropped; thresholds are re-checked against the surviving
+    /// subset alone, and a resolved group is returned immediately if it already satisfies them.
+    #[allow(dead_code)]
+    pub fn refresh(&mut self, old_request: Request, new_request: Request, surviving: Set<Name>)
+        -> Option<(Request, Vec<IdType>)> {
+
+        let old = match self.cache.remove(&old_request) {
+            Some(accumulation) => accumulation,
+            None => return None,
+        };
+
+        // A flagged sender's claims and keys are dropped the instant it's detected (see
+        // `add_identities`), so it never has an entry left in `old.claims` to iterate over below —
+        // the exclusion has to be seeded directly from `old.byzantine`, not rediscovered there.
+        let new_byzantine = old.byzantine.intersection(&surviving).cloned().collect::<Set<_>>();
+
+        // Carry `old.keys` over as-is rather than rebuilding it from `old.claims`: those entries
+        // already passed whatever verification `add_identities`/`add_identities_verified` applied
+        // when they were added (e.g. checked against `trusted_commitment`), and reinserting
+        // straight from raw claims would silently re-admit a key that was never proven to belong
+        // to the group. A departed or byzantine sender's contributions are stripped via
+        // `remove_keys_from` instead.
+        let mut new_keys = old.keys;
+        let mut new_claims = Map::new();
+
+        for (sender, claims) in old.claims {
+            if !surviving.contains(&sender) || old.byzantine.contains(&sender) {
+                new_keys.remove_keys_from(&sender);
+                continue;
+            }
+            new_claims.insert(sender, claims);
+        }
+
+        let selection = Self::try_selecting_group(&mut new_keys, &new_claims, &new_byzantine, self.claim_threshold)
+            .map(|ids| (new_request.clone(),
+                        ids.into_iter().filter(|id| surviving.contains(&id.name())).collect()));
+
+        if selection.is_none() {
+            self.cache.insert(new_request, Accumulation {
+                keys: new_keys,
+                claims: new_claims,
+                inserted: Instant::now(),
+                byzantine: new_byzantine,
+                commitment_votes: Map::new(),
+                trusted_commitment: None,
+            });
+        }
+
+        selection
+    }
+
+    /// `true` if `sender` already vouched for a claim in this round that differs from `claim` —
+    /// i.e. `sender` is voting for two conflicting group compositions for the same request.
+    fn equivocates(claims: &Map<Name, Set<GroupClaim>>, sender: &Name, claim: &GroupClaim) -> bool {
+        claims.get(sender)
+              .map(|existing| !existing.contains(claim))
+              .unwrap_or(false)
     }
 
     fn try_selecting_group(key_store: &mut KeyStore<Name>,
                            claims: &Map<Name, Set<GroupClaim>>,
+                           byzantine: &Set<Name>,
                            claim_threshold: usize) -> Option<Vec<IdType>> {
 
-        let verified_claims = claims.iter().filter_map(|(name, claims)| {
+        let verified_claims = claims.iter()
+            .filter(|&(name, _)| !byzantine.contains(name))
+            .filter_map(|(name, claims)| {
             for claim in claims {
                 if Self::verify_claim(name, key_store, claim) {
                     return Some(claim);
@@ -133,8 +489,10 @@ mod test {
     use super::*;
     use rand::random;
     use sodiumoxide::crypto;
+    use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
     use std::cmp::Ordering;
     use std::fmt;
+    use std::thread::sleep;
 
     const NAMESIZE: usize = 64;
     const CLAIMS_THRESHOLD: usize = 10;
@@ -247,6 +605,10 @@ mod test {
         fn verify_public_key(&self, public_key: &crypto::sign::PublicKey) -> bool {
             crypto::sign::verify_detached(&self.signature, &self.serialised_message, public_key)
         }
+
+        fn dealer_commitments(&self) -> Vec<Vec<crypto::sign::PublicKey>> {
+            Vec::new()
+        }
     }
 
 #[test]
@@ -265,10 +627,314 @@ mod test {
         for index in 0..KEYS_THRESHOLD + 1 {
             let group_claim = TestGroupClaim::new(random_message.clone(), tuples[index].2.clone(), name_pubs.clone());
             if index < KEYS_THRESHOLD {
-                assert!(sentinel.add_identities(request.clone(), tuples[index].0.clone(), group_claim).is_none());
+                assert!(sentinel.add_identities(request.clone(), tuples[index].0.clone(), group_claim).0.is_none());
                 continue;
             }
-            assert!(sentinel.add_identities(request.clone(), tuples[KEYS_THRESHOLD].0.clone(), group_claim).is_some());
+            assert!(sentinel.add_identities(request.clone(), tuples[KEYS_THRESHOLD].0.clone(), group_claim).0.is_some());
+        }
+    }
+
+    #[test]
+    fn add_identities_flags_equivocating_sender() {
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestIdType, TestGroupClaim> = KeySentinel::new(CLAIMS_THRESHOLD, KEYS_THRESHOLD);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+        let sender = generate_random_name();
+        let key_pair = crypto::sign::gen_keypair();
+
+        let first_message = generate_random_name().data;
+        let first_signature = crypto::sign::sign_detached(&first_message, &key_pair.1);
+        let first_claim = TestGroupClaim::new(first_message, first_signature, Vec::new());
+
+        let (selection, equivocators) = sentinel.add_identities(request.clone(), sender.clone(), first_claim);
+        assert!(selection.is_none());
+        assert!(equivocators.is_empty());
+
+        let second_message = generate_random_name().data;
+        let second_signature = crypto::sign::sign_detached(&second_message, &key_pair.1);
+        let second_claim = TestGroupClaim::new(second_message, second_signature, Vec::new());
+
+        let (selection, equivocators) = sentinel.add_identities(request.clone(), sender.clone(), second_claim.clone());
+        assert!(selection.is_none());
+        assert!(equivocators.contains(&sender));
+
+        // Exclusion is idempotent: resubmitting again leaves the sender excluded.
+        let (_, equivocators) = sentinel.add_identities(request, sender.clone(), second_claim);
+        assert!(equivocators.contains(&sender));
+    }
+
+    #[test]
+    fn refresh_carries_over_surviving_members_towards_the_new_quorum() {
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestIdType, TestGroupClaim> = KeySentinel::new(CLAIMS_THRESHOLD, KEYS_THRESHOLD);
+        let random_message = generate_random_name().data;
+        let mut tuples = Vec::new();
+        for _ in 0..KEYS_THRESHOLD + 1 {
+            let key_pair = crypto::sign::gen_keypair();
+            let signature = crypto::sign::sign_detached(&random_message, &key_pair.1);
+            tuples.push((generate_random_name(), key_pair.0, signature));
+        }
+
+        let old_request = TestRequest::new(random::<usize>(), generate_random_name());
+        let name_pubs = tuples.iter().map(|&(ref name, ref public_key, _)| TestIdType { name: name.clone(), public_key: public_key.clone() }).collect::<Vec<_>>();
+        for index in 0..KEYS_THRESHOLD {
+            let group_claim = TestGroupClaim::new(random_message.clone(), tuples[index].2.clone(), name_pubs.clone());
+            assert!(sentinel.add_identities(old_request.clone(), tuples[index].0.clone(), group_claim).0.is_none());
+        }
+
+        let surviving = tuples.iter().map(|&(ref name, _, _)| name.clone()).collect::<Set<_>>();
+        let new_request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        // Only the already-accumulated (still under quorum) members carried over — not enough on
+        // their own to resolve the new request yet.
+        assert!(sentinel.refresh(old_request, new_request.clone(), surviving).is_none());
+
+        // The final member's claim, submitted fresh against the new request, now tips the merged
+        // accumulation over the quorum.
+        let group_claim = TestGroupClaim::new(random_message, tuples[KEYS_THRESHOLD].2.clone(), name_pubs);
+        assert!(sentinel.add_identities(new_request, tuples[KEYS_THRESHOLD].0.clone(), group_claim).0.is_some());
+    }
+
+    #[test]
+    fn cleanup_evicts_accumulations_older_than_the_expiry_duration() {
+        let expiry_duration = Duration::from_millis(20);
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestIdType, TestGroupClaim>
+            = KeySentinel::with_expiry_duration(CLAIMS_THRESHOLD, KEYS_THRESHOLD, expiry_duration);
+
+        let stale_request = TestRequest::new(random::<usize>(), generate_random_name());
+        let key_pair = crypto::sign::gen_keypair();
+        let message = generate_random_name().data;
+        let signature = crypto::sign::sign_detached(&message, &key_pair.1);
+        let claim = TestGroupClaim::new(message, signature, Vec::new());
+        assert!(sentinel.add_identities(stale_request.clone(), generate_random_name(), claim).0.is_none());
+
+        sleep(expiry_duration * 2);
+
+        let fresh_request = TestRequest::new(random::<usize>(), generate_random_name());
+        let fresh_key_pair = crypto::sign::gen_keypair();
+        let fresh_message = generate_random_name().data;
+        let fresh_signature = crypto::sign::sign_detached(&fresh_message, &fresh_key_pair.1);
+        let fresh_claim = TestGroupClaim::new(fresh_message, fresh_signature, Vec::new());
+        assert!(sentinel.add_identities(fresh_request.clone(), generate_random_name(), fresh_claim).0.is_none());
+
+        // Only the accumulation older than `expiry_duration` timed out; the one started just now
+        // is left alone.
+        assert_eq!(sentinel.cleanup(), vec![stale_request]);
+    }
+
+    #[test]
+    fn cleanup_is_a_no_op_without_a_configured_expiry_duration() {
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestIdType, TestGroupClaim>
+            = KeySentinel::new(CLAIMS_THRESHOLD, KEYS_THRESHOLD);
+
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+        let key_pair = crypto::sign::gen_keypair();
+        let message = generate_random_name().data;
+        let signature = crypto::sign::sign_detached(&message, &key_pair.1);
+        let claim = TestGroupClaim::new(message, signature, Vec::new());
+        assert!(sentinel.add_identities(request, generate_random_name(), claim).0.is_none());
+
+        assert!(sentinel.cleanup().is_empty());
+    }
+
+    #[test]
+    fn with_expiry_duration_and_capacity_evicts_by_capacity_pressure() {
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestIdType, TestGroupClaim>
+            = KeySentinel::with_expiry_duration_and_capacity(2, 1, Duration::from_secs(60), 1);
+
+        let first_request = TestRequest::new(random::<usize>(), generate_random_name());
+        let first_sender = generate_random_name();
+        let first_key_pair = crypto::sign::gen_keypair();
+        let first_message = generate_random_name().data;
+        let first_signature = crypto::sign::sign_detached(&first_message, &first_key_pair.1);
+        let first_claim = TestGroupClaim::new(first_message, first_signature, Vec::new());
+        assert!(sentinel.add_identities(first_request.clone(), first_sender.clone(), first_claim).0.is_none());
+
+        // Inserting a second request's accumulation, with the cache bounded to capacity 1, evicts
+        // the first's.
+        let second_request = TestRequest::new(random::<usize>(), generate_random_name());
+        let second_key_pair = crypto::sign::gen_keypair();
+        let second_message = generate_random_name().data;
+        let second_signature = crypto::sign::sign_detached(&second_message, &second_key_pair.1);
+        let second_claim = TestGroupClaim::new(second_message, second_signature, Vec::new());
+        assert!(sentinel.add_identities(second_request, generate_random_name(), second_claim).0.is_none());
+
+        // A differing claim from the first sender under the first request now starts a fresh
+        // accumulation: were the first claim still accumulated, the sender would be flagged as an
+        // equivocator.
+        let third_key_pair = crypto::sign::gen_keypair();
+        let third_message = generate_random_name().data;
+        let third_signature = crypto::sign::sign_detached(&third_message, &third_key_pair.1);
+        let third_claim = TestGroupClaim::new(third_message, third_signature, Vec::new());
+        let (selection, equivocators) = sentinel.add_identities(first_request, first_sender, third_claim);
+        assert!(selection.is_none());
+        assert!(equivocators.is_empty());
+    }
+
+    #[test]
+    fn refresh_keeps_a_previously_flagged_equivocator_excluded() {
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestIdType, TestGroupClaim> = KeySentinel::new(CLAIMS_THRESHOLD, KEYS_THRESHOLD);
+        let old_request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        let flagged_sender = generate_random_name();
+        let flagged_key_pair = crypto::sign::gen_keypair();
+        let first_message = generate_random_name().data;
+        let first_signature = crypto::sign::sign_detached(&first_message, &flagged_key_pair.1);
+        let first_claim = TestGroupClaim::new(first_message, first_signature, Vec::new());
+        assert!(sentinel.add_identities(old_request.clone(), flagged_sender.clone(), first_claim).0.is_none());
+
+        let second_message = generate_random_name().data;
+        let second_signature = crypto::sign::sign_detached(&second_message, &flagged_key_pair.1);
+        let second_claim = TestGroupClaim::new(second_message, second_signature, Vec::new());
+        let (_, equivocators) = sentinel.add_identities(old_request.clone(), flagged_sender.clone(), second_claim);
+        assert!(equivocators.contains(&flagged_sender));
+
+        let surviving = vec![flagged_sender.clone(), generate_random_name()].into_iter().collect::<Set<_>>();
+        let new_request = TestRequest::new(random::<usize>(), generate_random_name());
+        assert!(sentinel.refresh(old_request, new_request.clone(), surviving).is_none());
+
+        // A previously flagged equivocator must not get a clean slate across churn: resubmitting
+        // against the fresh request still reports it as excluded.
+        let resubmitted_message = generate_random_name().data;
+        let resubmitted_signature = crypto::sign::sign_detached(&resubmitted_message, &flagged_key_pair.1);
+        let resubmitted_claim = TestGroupClaim::new(resubmitted_message, resubmitted_signature, Vec::new());
+        let (_, equivocators) = sentinel.add_identities(new_request, flagged_sender.clone(), resubmitted_claim);
+        assert!(equivocators.contains(&flagged_sender));
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+    struct TestVssIdentity {
+        name: TestName,
+        public_key: TestName, // compressed Edwards point, reusing TestName as an opaque byte blob
+        share_index: u64,
+    }
+
+    impl IdTrait<TestName> for TestVssIdentity {
+        fn name(&self) -> TestName {
+            self.name.clone()
+        }
+
+        fn public_key(&self) -> crypto::sign::PublicKey {
+            crypto::sign::PublicKey::from_slice(&self.public_key.data).unwrap()
+        }
+    }
+
+    impl VssIdTrait<TestName> for TestVssIdentity {
+        fn share_index(&self) -> u64 {
+            self.share_index
+        }
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+    struct TestVssClaim {
+        identity: TestVssIdentity,
+        commitment: Vec<TestName>, // one dealer's phi_0, phi_1, each a compressed Edwards point
+    }
+
+    impl GroupClaimTrait<TestVssIdentity> for TestVssClaim {
+        fn group_identities(&self) -> Vec<TestVssIdentity> {
+            vec![self.identity.clone()]
+        }
+
+        fn verify_public_key(&self, _: &crypto::sign::PublicKey) -> bool {
+            true
         }
+
+        fn dealer_commitments(&self) -> Vec<Vec<crypto::sign::PublicKey>> {
+            vec![self.commitment.iter()
+                .map(|point| crypto::sign::PublicKey::from_slice(&point.data).unwrap())
+                .collect()]
+        }
+    }
+
+    fn edwards_to_name(point: &EdwardsPoint) -> TestName {
+        TestName { data: point.compress().as_bytes().to_vec() }
+    }
+
+    #[test]
+    fn add_identities_verified_rejects_a_lone_senders_self_asserted_commitment() {
+        let share_index = 3u64;
+
+        // Choose phi_1 freely, then back-solve phi_0 so the polynomial evaluates to `valid_key`
+        // at `share_index`: phi_0 + share_index * phi_1 == valid_key. A single equivocating
+        // sender can always do this for whatever key it wants to smuggle in — so its own
+        // self-asserted commitment must never be trusted on its own.
+        let valid_pair = crypto::sign::gen_keypair();
+        let valid_point = CompressedEdwardsY::from_slice(valid_pair.0.as_ref())
+            .decompress()
+            .unwrap();
+        let phi_1 = ED25519_BASEPOINT_POINT * Scalar::from(random::<u64>());
+        let phi_0 = valid_point - Scalar::from(share_index) * phi_1;
+        let commitment = vec![edwards_to_name(&phi_0), edwards_to_name(&phi_1)];
+
+        let identity = TestVssIdentity {
+            name: generate_random_name(),
+            public_key: edwards_to_name(&valid_point),
+            share_index: share_index,
+        };
+        let claim = TestVssClaim { identity: identity.clone(), commitment: commitment };
+
+        // keys_threshold = 2: a lone sender's assertion is one vote short of corroboration, even
+        // though claim_threshold = 1 would otherwise resolve the group immediately.
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestVssIdentity, TestVssClaim>
+            = KeySentinel::new(1, 2);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+        assert!(sentinel.add_identities_verified(request, identity.name(), claim).0.is_none());
+    }
+
+    #[test]
+    fn add_identities_verified_resolves_once_independent_senders_corroborate_the_commitment() {
+        // A real degree-1 polynomial phi_0 + phi_1 * x, shared by two members at indices 1 and 2.
+        let phi_0 = ED25519_BASEPOINT_POINT * Scalar::from(random::<u64>());
+        let phi_1 = ED25519_BASEPOINT_POINT * Scalar::from(random::<u64>());
+        let commitment = vec![edwards_to_name(&phi_0), edwards_to_name(&phi_1)];
+
+        let share_at = |index: u64| phi_0 + phi_1 * Scalar::from(index);
+        let first = TestVssIdentity {
+            name: generate_random_name(),
+            public_key: edwards_to_name(&share_at(1)),
+            share_index: 1,
+        };
+        let second = TestVssIdentity {
+            name: generate_random_name(),
+            public_key: edwards_to_name(&share_at(2)),
+            share_index: 2,
+        };
+
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestVssIdentity, TestVssClaim>
+            = KeySentinel::new(2, 2);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        let first_claim = TestVssClaim { identity: first.clone(), commitment: commitment.clone() };
+        assert!(sentinel.add_identities_verified(request.clone(), first.name(), first_claim).0.is_none());
+
+        let second_claim = TestVssClaim { identity: second.clone(), commitment: commitment };
+        assert!(sentinel.add_identities_verified(request, second.name(), second_claim).0.is_some());
+    }
+
+    #[test]
+    fn refresh_does_not_resurrect_a_key_that_never_passed_vss_verification() {
+        // keys_threshold = 2: a lone sender's commitment is never trusted, so `old.keys` stays
+        // empty even though `old.claims` holds the sender's (unverified) self-asserted key.
+        let mut sentinel: KeySentinel<TestRequest, TestName, TestVssIdentity, TestVssClaim>
+            = KeySentinel::new(1, 2);
+        let old_request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        let phi_0 = ED25519_BASEPOINT_POINT * Scalar::from(random::<u64>());
+        let phi_1 = ED25519_BASEPOINT_POINT * Scalar::from(random::<u64>());
+        let commitment = vec![edwards_to_name(&phi_0), edwards_to_name(&phi_1)];
+        let lone_sender = TestVssIdentity {
+            name: generate_random_name(),
+            public_key: edwards_to_name(&(phi_0 + phi_1 * Scalar::from(1u64))),
+            share_index: 1,
+        };
+        let claim = TestVssClaim { identity: lone_sender.clone(), commitment: commitment };
+        assert!(sentinel.add_identities_verified(old_request.clone(), lone_sender.name(), claim).0.is_none());
+
+        let surviving = vec![lone_sender.name()].into_iter().collect::<Set<_>>();
+        let new_request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        // Were the migration rebuilding `new_keys` straight from `old.claims`, `lone_sender`'s own
+        // self-asserted key would satisfy `TestVssClaim::verify_public_key` (always `true`) and
+        // resolve the group here, even though its commitment was never corroborated.
+        assert!(sentinel.refresh(old_request, new_request, surviving).is_none());
     }
 }
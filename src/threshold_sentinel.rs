@@ -0,0 +1,632 @@
+// Copyright 2015 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under (1) the MaidSafe.net Commercial License,
+// version 1.0 or later, or (2) The General Public License (GPL), version 3, depending on which
+// licence you accepted on initial access to the Software (the "Licences").
+//
+// By contributing code to the SAFE Network Software, or to this project generally, you agree to be
+// bound by the terms of the MaidSafe Contributor Agreement, version 1.0.  This, along with the
+// Licenses can be found in the root directory of this project at LICENSE, COPYING and CONTRIBUTOR.
+//
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.
+//
+// Please review the Licences for the specific language governing permissions and limitations
+// relating to use of the SAFE Network Software.
+
+use lru_time_cache::LruCache;
+use sodiumoxide::crypto::sign;
+use sha2::{Digest, Sha512};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use std::collections::BTreeMap;
+use key_store::KeyStore;
+
+#[allow(dead_code)]
+const MAX_REQUEST_COUNT: usize = 1000;
+
+type Map<K,V> = BTreeMap<K,V>;
+
+/// One participant's contribution to a threshold Schnorr (FROST-style) signature: their round-1
+/// nonce commitments `(D_i, E_i)` and round-2 response scalar `z_i`, plus the data needed to bind
+/// and verify that response against the group's joint public key.
+pub trait PartialSigTrait<Name> {
+    fn signer(&self) -> Name;
+    fn signer_index(&self) -> u64;
+    fn message(&self) -> Vec<u8>;
+    fn nonce_d(&self) -> sign::PublicKey;
+    fn nonce_e(&self) -> sign::PublicKey;
+    fn response(&self) -> [u8; 32];
+    fn public_share(&self) -> sign::PublicKey;
+}
+
+/// A completed aggregate Schnorr signature `(R, z)`, verifiable against the group's joint public
+/// key without learning any individual signer's contribution.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AggregateSignature {
+    pub r: sign::PublicKey,
+    pub z: [u8; 32],
+}
+
+/// Why a partial, or the aggregate it completed, was rejected.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AggregationError<Request, Name> {
+    /// `Name`'s own contribution was rejected: it failed to verify against its claimed nonces or
+    /// share, named a different message than the partials already held, or reused a signer index
+    /// already held by another signer.
+    BadSigner(Name),
+    /// Every signer's individual check passed, but the assembled signature does not verify
+    /// against the configured group public key. No single signer can be blamed for this, so the
+    /// request itself is named instead.
+    AggregateMismatch(Request),
+}
+
+#[allow(dead_code)]
+pub struct ThresholdSentinel<Request, Name, PartialSig>
+        where Request: Eq + PartialOrd + Ord + Clone,
+              Name:    Eq + PartialOrd + Ord + Clone,
+              PartialSig: Clone + PartialSigTrait<Name>, {
+    cache: LruCache<Request, (KeyStore<Name>, Map<Name, PartialSig>)>,
+    claim_threshold: usize,
+    keys_threshold: usize,
+    group_public_key: sign::PublicKey,
+}
+
+impl<Request, Name, PartialSig> ThresholdSentinel<Request, Name, PartialSig>
+    where Request: Eq + PartialOrd + Ord + Clone,
+          Name:    Eq + PartialOrd + Ord + Clone,
+          PartialSig: Clone + PartialSigTrait<Name>, {
+
+    #[allow(dead_code)]
+    pub fn new(claim_threshold: usize, keys_threshold: usize, group_public_key: sign::PublicKey)
+            -> ThresholdSentinel<Request, Name, PartialSig> {
+        ThresholdSentinel {
+            cache: LruCache::with_capacity(MAX_REQUEST_COUNT),
+            claim_threshold: claim_threshold,
+            keys_threshold: keys_threshold,
+            group_public_key: group_public_key,
+        }
+    }
+
+    /// Accumulates one signer's partial signature for `request`. Returns the aggregate signature
+    /// once `claim_threshold` partials agreeing on the same message have arrived, or `Err` naming
+    /// either the signer whose contribution was rejected (see `AggregationError::BadSigner`), or
+    /// the request itself if the completed aggregate does not verify against the group's public key.
+    #[allow(dead_code)]
+    pub fn add_partial(&mut self, request: Request, partial: PartialSig)
+        -> Result<Option<(Request, AggregateSignature)>, AggregationError<Request, Name>> {
+
+        let signer = partial.signer();
+
+        let retval = {
+            let keys_threshold = self.keys_threshold;
+            let keys_and_partials
+                = self.cache.entry(request.clone())
+                            .or_insert_with(||(KeyStore::new(keys_threshold), Map::new()));
+
+            let ref mut keys     = &mut keys_and_partials.0;
+            let ref mut partials = &mut keys_and_partials.1;
+
+            if partials.contains_key(&signer) {
+                return Ok(None);
+            }
+
+            if let Some(existing) = partials.values().next() {
+                if existing.message() != partial.message() {
+                    return Err(AggregationError::BadSigner(signer));
+                }
+            }
+
+            // A second Name claiming an index already held by someone else would corrupt every
+            // other present signer's Lagrange coefficient (lagrange_coefficient skips both
+            // occurrences of the value) and collapse index_to_name's mapping for that index.
+            if partials.values().any(|p| p.signer_index() == partial.signer_index()) {
+                return Err(AggregationError::BadSigner(signer));
+            }
+
+            keys.add_key(signer.clone(), signer.clone(), partial.public_share());
+            partials.insert(signer.clone(), partial);
+
+            match Self::try_aggregating(partials, self.claim_threshold, &self.group_public_key) {
+                Ok(signature) => signature.map(|sig| (request.clone(), sig)),
+                Err(Some(offender)) => return Err(AggregationError::BadSigner(offender)),
+                Err(None) => return Err(AggregationError::AggregateMismatch(request.clone())),
+            }
+        };
+
+        Ok(retval.map(|(request, signature)| {
+            self.cache.remove(&request);
+            (request, signature)
+        }))
+    }
+
+    /// `Err(Some(name))` blames `name`'s own contribution; `Err(None)` means every individual
+    /// check passed but the assembled signature still doesn't verify against `group_public_key`.
+    fn try_aggregating(partials: &Map<Name, PartialSig>,
+                        claim_threshold: usize,
+                        group_public_key: &sign::PublicKey)
+        -> Result<Option<AggregateSignature>, Option<Name>> {
+
+        if partials.len() < claim_threshold {
+            return Ok(None);
+        }
+
+        let message = partials.values().next().expect("checked len above").message();
+
+        // B: the sorted (index, D_i, E_i) triples of every present signer, binding each rho_i to
+        // the full signer set so one participant can't reuse nonces across a different subgroup.
+        let mut binding_list = partials.values()
+            .map(|p| (p.signer_index(), p.nonce_d(), p.nonce_e()))
+            .collect::<Vec<_>>();
+        binding_list.sort_by_key(|&(index, _, _)| index);
+
+        let index_to_name = partials.iter()
+            .map(|(name, p)| (p.signer_index(), name.clone()))
+            .collect::<Map<_, _>>();
+
+        let indices = binding_list.iter().map(|&(index, _, _)| index).collect::<Vec<_>>();
+        let group_point = decompress(group_public_key)
+            .expect("the configured group public key must be a valid point");
+
+        let mut group_commitment = identity();
+        let mut binding_factors = Map::new();
+        for &(index, ref d, ref e) in &binding_list {
+            let rho = binding_factor(index, &message, &binding_list);
+            let point_d = match decompress(d) {
+                Some(point) => point,
+                None => return Err(Some(index_to_name[&index].clone())),
+            };
+            let point_e = match decompress(e) {
+                Some(point) => point,
+                None => return Err(Some(index_to_name[&index].clone())),
+            };
+            group_commitment = group_commitment + point_d + point_e * rho;
+            binding_factors.insert(index, rho);
+        }
+
+        let challenge = challenge_scalar(&group_commitment, &group_point, &message);
+
+        let mut z = Scalar::zero();
+        for (name, partial) in partials {
+            let index = partial.signer_index();
+            let rho = binding_factors[&index];
+            let lambda = lagrange_coefficient(index, &indices);
+            let z_i = Scalar::from_bits(partial.response());
+
+            let point_d = match decompress(&partial.nonce_d()) {
+                Some(point) => point,
+                None => return Err(Some(name.clone())),
+            };
+            let point_e = match decompress(&partial.nonce_e()) {
+                Some(point) => point,
+                None => return Err(Some(name.clone())),
+            };
+            let point_share = match decompress(&partial.public_share()) {
+                Some(point) => point,
+                None => return Err(Some(name.clone())),
+            };
+
+            let expected = point_d + point_e * rho + point_share * (challenge * lambda);
+
+            if RISTRETTO_BASEPOINT_POINT * z_i != expected {
+                return Err(Some(name.clone()));
+            }
+            z = z + z_i;
+        }
+
+        // Each signer's own check above only confirms `z_i` is consistent with the `Y_i` it
+        // supplied, and `Y_i` is attacker-controlled, not pinned to the real DKG roster. The
+        // acceptance criterion is this aggregate check against the configured group key: a
+        // forged-but-self-consistent set of partials will fail it even though every per-signer
+        // check above passed, and no single signer can be blamed for that.
+        if RISTRETTO_BASEPOINT_POINT * z != group_commitment + challenge * group_point {
+            return Err(None);
+        }
+
+        Ok(Some(AggregateSignature {
+            r: compress(&group_commitment),
+            z: z.to_bytes(),
+        }))
+    }
+}
+
+fn identity() -> RistrettoPoint {
+    RISTRETTO_BASEPOINT_POINT * Scalar::zero()
+}
+
+/// `None` if `key`'s 32 bytes are not the canonical encoding of a Ristretto point — a remote
+/// signer's nonce or public share is attacker-controlled and must be rejected, not unwrapped.
+fn decompress(key: &sign::PublicKey) -> Option<RistrettoPoint> {
+    CompressedRistretto::from_slice(key.as_ref()).decompress()
+}
+
+fn compress(point: &RistrettoPoint) -> sign::PublicKey {
+    sign::PublicKey::from_slice(point.compress().as_bytes())
+        .expect("a compressed Ristretto point is always 32 bytes")
+}
+
+fn hash_to_scalar(data: &[u8]) -> Scalar {
+    Scalar::from_hash(Sha512::new().chain(data))
+}
+
+fn binding_factor(index: u64,
+                   message: &[u8],
+                   binding_list: &[(u64, sign::PublicKey, sign::PublicKey)]) -> Scalar {
+    let mut preimage = index.to_le_bytes().to_vec();
+    preimage.extend_from_slice(message);
+    for &(i, ref d, ref e) in binding_list {
+        preimage.extend_from_slice(&i.to_le_bytes());
+        preimage.extend_from_slice(d.as_ref());
+        preimage.extend_from_slice(e.as_ref());
+    }
+    hash_to_scalar(&preimage)
+}
+
+fn challenge_scalar(r: &RistrettoPoint, y: &RistrettoPoint, message: &[u8]) -> Scalar {
+    let mut preimage = r.compress().as_bytes().to_vec();
+    preimage.extend_from_slice(y.compress().as_bytes());
+    preimage.extend_from_slice(message);
+    hash_to_scalar(&preimage)
+}
+
+fn lagrange_coefficient(index: u64, present: &[u64]) -> Scalar {
+    let index_scalar = Scalar::from(index);
+    let mut numerator = Scalar::one();
+    let mut denominator = Scalar::one();
+    for &other in present {
+        if other == index {
+            continue;
+        }
+        let other_scalar = Scalar::from(other);
+        numerator = numerator * other_scalar;
+        denominator = denominator * (other_scalar - index_scalar);
+    }
+    numerator * denominator.invert()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::random;
+
+    const NAMESIZE: usize = 32;
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+    struct TestName {
+        data: Vec<u8>
+    }
+
+    fn generate_random_name() -> TestName {
+        let mut arr = [0u8; NAMESIZE];
+        for i in (0..NAMESIZE) { arr[i] = random::<u8>(); }
+        TestName { data: arr.to_vec() }
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+    struct TestRequest {
+        core: usize,
+        name: TestName
+    }
+
+    impl TestRequest {
+        pub fn new(core: usize, name: TestName) -> TestRequest {
+            TestRequest { core: core, name: name }
+        }
+    }
+
+    #[derive(Clone)]
+    struct TestPartialSig {
+        signer: TestName,
+        signer_index: u64,
+        message: Vec<u8>,
+        nonce_d: sign::PublicKey,
+        nonce_e: sign::PublicKey,
+        response: [u8; 32],
+        public_share: sign::PublicKey,
+    }
+
+    impl PartialSigTrait<TestName> for TestPartialSig {
+        fn signer(&self) -> TestName { self.signer.clone() }
+        fn signer_index(&self) -> u64 { self.signer_index }
+        fn message(&self) -> Vec<u8> { self.message.clone() }
+        fn nonce_d(&self) -> sign::PublicKey { self.nonce_d }
+        fn nonce_e(&self) -> sign::PublicKey { self.nonce_e }
+        fn response(&self) -> [u8; 32] { self.response }
+        fn public_share(&self) -> sign::PublicKey { self.public_share }
+    }
+
+    #[test]
+    fn add_partial_aggregates_a_signature_that_verifies() {
+        // A degree-(n-1) Shamir polynomial f(x) = secret + a1*x + a2*x^2: with every signer
+        // present, interpolating at x = 0 recovers `secret` exactly.
+        let secret = Scalar::from(random::<u64>());
+        let a1 = Scalar::from(random::<u64>());
+        let a2 = Scalar::from(random::<u64>());
+        let share = |index: u64| -> Scalar {
+            let x = Scalar::from(index);
+            secret + a1 * x + a2 * x * x
+        };
+
+        let group_public_key = compress(&(RISTRETTO_BASEPOINT_POINT * secret));
+        let message = generate_random_name().data;
+        let signers = (1u64..4).map(|index| (generate_random_name(), index)).collect::<Vec<_>>();
+
+        let nonces = signers.iter()
+            .map(|&(_, index)| (index, Scalar::from(random::<u64>()), Scalar::from(random::<u64>())))
+            .collect::<Vec<_>>();
+
+        let binding_list = nonces.iter()
+            .map(|&(index, d, e)| (index,
+                                    compress(&(RISTRETTO_BASEPOINT_POINT * d)),
+                                    compress(&(RISTRETTO_BASEPOINT_POINT * e))))
+            .collect::<Vec<_>>();
+        let indices = binding_list.iter().map(|&(index, _, _)| index).collect::<Vec<_>>();
+
+        let mut group_commitment = identity();
+        let mut rhos = Map::new();
+        for &(index, ref d_pub, ref e_pub) in &binding_list {
+            let rho = binding_factor(index, &message, &binding_list);
+            group_commitment = group_commitment + decompress(d_pub).unwrap() + decompress(e_pub).unwrap() * rho;
+            rhos.insert(index, rho);
+        }
+
+        let group_point = decompress(&group_public_key).unwrap();
+        let challenge = challenge_scalar(&group_commitment, &group_point, &message);
+
+        let mut sentinel: ThresholdSentinel<TestRequest, TestName, TestPartialSig>
+            = ThresholdSentinel::new(3, 1, group_public_key);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        let mut result = None;
+        for (i, &(ref name, index)) in signers.iter().enumerate() {
+            let (_, d, e) = nonces[i];
+            let rho = rhos[&index];
+            let lambda = lagrange_coefficient(index, &indices);
+            let x_i = share(index);
+            let z_i = d + rho * e + challenge * lambda * x_i;
+
+            let partial = TestPartialSig {
+                signer: name.clone(),
+                signer_index: index,
+                message: message.clone(),
+                nonce_d: binding_list[i].1,
+                nonce_e: binding_list[i].2,
+                response: z_i.to_bytes(),
+                public_share: compress(&(RISTRETTO_BASEPOINT_POINT * x_i)),
+            };
+
+            result = sentinel.add_partial(request.clone(), partial).expect("genuine partial must verify");
+        }
+
+        let (_, signature) = result.expect("threshold reached, aggregate expected");
+
+        let r_point = decompress(&signature.r).unwrap();
+        let recomputed_challenge = challenge_scalar(&r_point, &group_point, &message);
+        let z = Scalar::from_bits(signature.z);
+        assert_eq!(RISTRETTO_BASEPOINT_POINT * z, r_point + recomputed_challenge * group_point);
+    }
+
+    #[test]
+    fn add_partial_rejects_and_names_a_forged_response() {
+        let secret = Scalar::from(random::<u64>());
+        let a1 = Scalar::from(random::<u64>());
+        let share = |index: u64| secret + a1 * Scalar::from(index);
+
+        let group_public_key = compress(&(RISTRETTO_BASEPOINT_POINT * secret));
+        let message = generate_random_name().data;
+
+        let signer_a = (generate_random_name(), 1u64);
+        let signer_b = (generate_random_name(), 2u64);
+        let indices = vec![signer_a.1, signer_b.1];
+
+        let (d_a, e_a) = (Scalar::from(random::<u64>()), Scalar::from(random::<u64>()));
+        let (d_b, e_b) = (Scalar::from(random::<u64>()), Scalar::from(random::<u64>()));
+
+        let binding_list = vec![
+            (signer_a.1, compress(&(RISTRETTO_BASEPOINT_POINT * d_a)), compress(&(RISTRETTO_BASEPOINT_POINT * e_a))),
+            (signer_b.1, compress(&(RISTRETTO_BASEPOINT_POINT * d_b)), compress(&(RISTRETTO_BASEPOINT_POINT * e_b))),
+        ];
+
+        let mut group_commitment = identity();
+        let mut rhos = Map::new();
+        for &(index, ref d_pub, ref e_pub) in &binding_list {
+            let rho = binding_factor(index, &message, &binding_list);
+            group_commitment = group_commitment + decompress(d_pub).unwrap() + decompress(e_pub).unwrap() * rho;
+            rhos.insert(index, rho);
+        }
+        let group_point = decompress(&group_public_key).unwrap();
+        let challenge = challenge_scalar(&group_commitment, &group_point, &message);
+
+        let mut sentinel: ThresholdSentinel<TestRequest, TestName, TestPartialSig>
+            = ThresholdSentinel::new(2, 1, group_public_key);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        let lambda_a = lagrange_coefficient(signer_a.1, &indices);
+        let z_a = d_a + rhos[&signer_a.1] * e_a + challenge * lambda_a * share(signer_a.1);
+        let partial_a = TestPartialSig {
+            signer: signer_a.0.clone(),
+            signer_index: signer_a.1,
+            message: message.clone(),
+            nonce_d: binding_list[0].1,
+            nonce_e: binding_list[0].2,
+            response: z_a.to_bytes(),
+            public_share: compress(&(RISTRETTO_BASEPOINT_POINT * share(signer_a.1))),
+        };
+        assert_eq!(sentinel.add_partial(request.clone(), partial_a).unwrap(), None);
+
+        // Signer B's response is unrelated to its nonces and share, so it can never satisfy the
+        // per-signer check: it must be rejected and named, not crash the aggregation.
+        let forged_z_b = Scalar::from(random::<u64>());
+        let partial_b = TestPartialSig {
+            signer: signer_b.0.clone(),
+            signer_index: signer_b.1,
+            message: message,
+            nonce_d: binding_list[1].1,
+            nonce_e: binding_list[1].2,
+            response: forged_z_b.to_bytes(),
+            public_share: compress(&(RISTRETTO_BASEPOINT_POINT * share(signer_b.1))),
+        };
+        assert_eq!(sentinel.add_partial(request, partial_b), Err(AggregationError::BadSigner(signer_b.0)));
+    }
+
+    #[test]
+    fn add_partial_rejects_self_consistent_partials_that_dont_verify_against_the_group_key() {
+        // Every partial here satisfies its own per-signer check against a `Y_i` the signer picked
+        // itself, but none of them were derived from a real share of `secret`: the aggregate must
+        // still be rejected, naming the request rather than any one signer.
+        let secret = Scalar::from(random::<u64>());
+        let group_public_key = compress(&(RISTRETTO_BASEPOINT_POINT * secret));
+        let message = generate_random_name().data;
+
+        let signer_a = (generate_random_name(), 1u64);
+        let signer_b = (generate_random_name(), 2u64);
+        let indices = vec![signer_a.1, signer_b.1];
+
+        let (d_a, e_a) = (Scalar::from(random::<u64>()), Scalar::from(random::<u64>()));
+        let (d_b, e_b) = (Scalar::from(random::<u64>()), Scalar::from(random::<u64>()));
+
+        let binding_list = vec![
+            (signer_a.1, compress(&(RISTRETTO_BASEPOINT_POINT * d_a)), compress(&(RISTRETTO_BASEPOINT_POINT * e_a))),
+            (signer_b.1, compress(&(RISTRETTO_BASEPOINT_POINT * d_b)), compress(&(RISTRETTO_BASEPOINT_POINT * e_b))),
+        ];
+
+        let mut group_commitment = identity();
+        let mut rhos = Map::new();
+        for &(index, ref d_pub, ref e_pub) in &binding_list {
+            let rho = binding_factor(index, &message, &binding_list);
+            group_commitment = group_commitment + decompress(d_pub).unwrap() + decompress(e_pub).unwrap() * rho;
+            rhos.insert(index, rho);
+        }
+        let group_point = decompress(&group_public_key).unwrap();
+        let challenge = challenge_scalar(&group_commitment, &group_point, &message);
+
+        let mut sentinel: ThresholdSentinel<TestRequest, TestName, TestPartialSig>
+            = ThresholdSentinel::new(2, 1, group_public_key);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        // Each signer picks its own z_i and solves Y_i = (z_i*G - D_i - rho*E_i) / (c*lambda),
+        // which makes its own per-signer check trivially true without knowing any real share.
+        let make_forged = |name: TestName, index: u64, d: Scalar, e: Scalar, bind: &(u64, sign::PublicKey, sign::PublicKey)| {
+            let rho = rhos[&index];
+            let lambda = lagrange_coefficient(index, &indices);
+            let z_i = Scalar::from(random::<u64>());
+            let y_i = (RISTRETTO_BASEPOINT_POINT * z_i - RISTRETTO_BASEPOINT_POINT * d
+                       - RISTRETTO_BASEPOINT_POINT * e * rho) * (challenge * lambda).invert();
+            TestPartialSig {
+                signer: name,
+                signer_index: index,
+                message: message.clone(),
+                nonce_d: bind.1,
+                nonce_e: bind.2,
+                response: z_i.to_bytes(),
+                public_share: compress(&y_i),
+            }
+        };
+
+        let partial_a = make_forged(signer_a.0, signer_a.1, d_a, e_a, &binding_list[0]);
+        assert_eq!(sentinel.add_partial(request.clone(), partial_a).unwrap(), None);
+
+        let partial_b = make_forged(signer_b.0, signer_b.1, d_b, e_b, &binding_list[1]);
+        assert_eq!(sentinel.add_partial(request.clone(), partial_b),
+                   Err(AggregationError::AggregateMismatch(request)));
+    }
+
+    #[test]
+    fn add_partial_ignores_a_duplicate_submission_from_the_same_signer() {
+        let group_public_key = sign::gen_keypair().0;
+        let mut sentinel: ThresholdSentinel<TestRequest, TestName, TestPartialSig>
+            = ThresholdSentinel::new(5, 1, group_public_key);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+        let signer = generate_random_name();
+        let message = generate_random_name().data;
+
+        let first = TestPartialSig {
+            signer: signer.clone(),
+            signer_index: 1,
+            message: message.clone(),
+            nonce_d: sign::gen_keypair().0,
+            nonce_e: sign::gen_keypair().0,
+            response: [0u8; 32],
+            public_share: sign::gen_keypair().0,
+        };
+        assert_eq!(sentinel.add_partial(request.clone(), first).unwrap(), None);
+
+        let second = TestPartialSig {
+            signer: signer.clone(),
+            signer_index: 1,
+            message: message,
+            nonce_d: sign::gen_keypair().0,
+            nonce_e: sign::gen_keypair().0,
+            response: [1u8; 32],
+            public_share: sign::gen_keypair().0,
+        };
+        // Resubmitting from the same signer is a no-op: it neither errors nor counts twice
+        // towards the claim threshold.
+        assert_eq!(sentinel.add_partial(request, second).unwrap(), None);
+    }
+
+    #[test]
+    fn add_partial_rejects_a_partial_for_a_different_message() {
+        let group_public_key = sign::gen_keypair().0;
+        let mut sentinel: ThresholdSentinel<TestRequest, TestName, TestPartialSig>
+            = ThresholdSentinel::new(2, 1, group_public_key);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+
+        let first = TestPartialSig {
+            signer: generate_random_name(),
+            signer_index: 1,
+            message: generate_random_name().data,
+            nonce_d: sign::gen_keypair().0,
+            nonce_e: sign::gen_keypair().0,
+            response: [0u8; 32],
+            public_share: sign::gen_keypair().0,
+        };
+        assert_eq!(sentinel.add_partial(request.clone(), first).unwrap(), None);
+
+        let second_signer = generate_random_name();
+        let second = TestPartialSig {
+            signer: second_signer.clone(),
+            signer_index: 2,
+            message: generate_random_name().data,
+            nonce_d: sign::gen_keypair().0,
+            nonce_e: sign::gen_keypair().0,
+            response: [0u8; 32],
+            public_share: sign::gen_keypair().0,
+        };
+        assert_eq!(sentinel.add_partial(request, second), Err(AggregationError::BadSigner(second_signer)));
+    }
+
+    #[test]
+    fn add_partial_rejects_a_different_signer_claiming_an_already_used_index() {
+        let group_public_key = sign::gen_keypair().0;
+        let mut sentinel: ThresholdSentinel<TestRequest, TestName, TestPartialSig>
+            = ThresholdSentinel::new(5, 1, group_public_key);
+        let request = TestRequest::new(random::<usize>(), generate_random_name());
+        let message = generate_random_name().data;
+
+        let first = TestPartialSig {
+            signer: generate_random_name(),
+            signer_index: 1,
+            message: message.clone(),
+            nonce_d: sign::gen_keypair().0,
+            nonce_e: sign::gen_keypair().0,
+            response: [0u8; 32],
+            public_share: sign::gen_keypair().0,
+        };
+        assert_eq!(sentinel.add_partial(request.clone(), first).unwrap(), None);
+
+        let colliding_signer = generate_random_name();
+        let second = TestPartialSig {
+            signer: colliding_signer.clone(),
+            signer_index: 1,
+            message: message,
+            nonce_d: sign::gen_keypair().0,
+            nonce_e: sign::gen_keypair().0,
+            response: [0u8; 32],
+            public_share: sign::gen_keypair().0,
+        };
+        assert_eq!(sentinel.add_partial(request, second), Err(AggregationError::BadSigner(colliding_signer)));
+    }
+}